@@ -1,13 +1,41 @@
 mod sal_server;
 
 use sal_server::SalServer;
+use sal_server::response::Response;
+use sal_server::router::Router;
 use std::collections::HashMap;
 use std::env::var;
 
 fn main() {
 
     let port = var("PORT").unwrap_or(String::from("8888")).parse();
-    SalServer::new(("0.0.0.0", port.unwrap_or(8888)), 8).route_http(route);
+    let server = SalServer::new(("0.0.0.0", port.unwrap_or(8888)), 8)
+        .enable_inactive_release(30);
+
+    // 以 MODE 选择服务模式，使 route_http、route_http_reactor、route_with 与 serve_dir 都被实际使用。
+    match var("MODE").unwrap_or_default().as_str() {
+
+        // 单反应堆事件循环
+        "reactor" => server.route_http_reactor(route),
+
+        // 内置静态文件服务（条件请求）
+        "static" => server.serve_dir("/static", "./public"),
+
+        // 按「方法 + 路径」分发
+        "router" => {
+            let router = Router::new()
+                .route("GET", "/", |line, head, _params, body| route(line, head, body))
+                .route("GET", "/user/:id", |_line, _head, params, _body| {
+                    let id = params.get("id").map(String::as_str).unwrap_or("");
+                    Response::new(200).body(format!("user: {id}\r\n")).finish(true)
+                });
+            server.route_with(router);
+        },
+
+        // 默认：阻塞式「一连接一线程」
+        _ => server.route_http(route),
+
+    };
 
 }
 
@@ -22,20 +50,8 @@ fn route(http_line: (&str, &str), head: HashMap<&str, &str>, body: &str) -> (Vec
         )
     };
 
-    let mut buf = Vec::from(format!(
-        "HTTP/1.1 200 OK\r\n\
-        Content-Type: text/plain; charset=utf-8\r\n\
-        Content-Length: {}\r\n\r\n", val.len()
-    ));
-
-    buf.extend(Vec::from(val));
-
-    if let Some(live) = head.get("Connection") {
-        if live == &"close" {
-            return (buf, false);
-        };
-    };
+    let keep_alive = head.get("Connection") != Some(&"close");
 
-    (buf, true)
+    Response::new(200).body(val).finish(keep_alive)
 
 }
@@ -1,288 +1,783 @@
-//!
-//! 这是一个简易的略有性能的轻量级服务器
-//!
-
-mod thread_limit;
-
-use std::collections::HashMap;
-use std::io::{BufRead, BufReader, BufWriter, Write};
-use std::net::{TcpListener, TcpStream};
-use std::panic::UnwindSafe;
-use self::thread_limit::ThreadLimit;
-
-///
-/// 服务器实例结构体
-///
-/// 用于储存 **线程（thread）** 和 **监听（listener）** 信息
-///
-/// - thread: ThreadLimit
-/// - listener: TcpListener
-///
-/// **Example:**
-/// ```
-/// mod salfa_server;
-/// use salfa_server::SalServer;
-/// ```
-///
-pub struct SalServer {
-    thread: ThreadLimit,
-    listener: TcpListener,
-}
-
-impl SalServer {
-
-    ///
-    /// 创建一个新的 `SalServer` 实例
-    ///
-    /// 参数：
-    /// - bind_path: 绑定地址，如：127.0.0.1:8888
-    /// - thread: 线程数量。注意不能为0，否则将***无限期阻塞***
-    ///
-    /// 返回一个新的 `SalServer` 结构体
-    ///
-    /// **Example:**
-    /// ```
-    /// mod salfa_server;
-    /// use salfa_server::SalServer;
-    ///
-    /// let server = SalServer::new("0.0.0.0:8888", 16);
-    /// ```
-    ///
-    pub fn new(bind_path: &str, thread: usize) -> SalServer {
-        let thread = ThreadLimit::new(thread);
-        let listener = TcpListener::bind(bind_path).expect("Error: Couldn't bind port!");
-        SalServer { thread, listener }
-    }
-
-    ///
-    /// 为服务提供路由，并提供服务（原始方法）
-    ///
-    /// 参数：
-    /// - route: 路由函数
-    ///
-    /// 使用该方法，需要定义一个特殊函数：
-    /// ```
-    /// fn route(buffer: Vec<u8>) -> (Vec<u8>, bool) {}
-    /// ```
-    /// 参数：
-    /// - buffer: 每次请求的原始数据
-    ///
-    /// 返回一个元组 `(Vec<u8>, bool)`
-    /// - Vec<u8>: 写入流数据所需的原始数据
-    /// - bool: 是否保持持续连接 (`Keep-Alive`)
-    ///
-    /// 该函数的 `buffer` 参数由 `route_pro` 方法提供
-    ///
-    /// **Example1:**
-    /// ```
-    /// mod salfa_server;
-    /// use salfa_server::SalServer;
-    ///
-    /// let server = SalServer::new("127.0.0.1:8888", 16);
-    /// server.route_pro(|buffer| {
-    ///     let mut buf = Vec::from(
-    ///         "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n"
-    ///     );
-    ///     buf.extend_from_slice(buffer);
-    ///     return (buf, false);
-    /// });
-    /// ```
-    ///
-    /// **Example 2:**
-    /// ```
-    /// mod salfa_server;
-    /// use salfa_server::SalServer;
-    ///
-    /// let server = SalServer::new("127.0.0.1:8888", 16);
-    /// server.route_pro(route);
-    ///
-    /// fn route(buffer: Vec<u8>) -> (Vec<u8>, bool) {
-    ///     (Vec::from("HTTP/1.1 200 OK\r\n\r\n"), true)
-    /// };
-    /// ```
-    ///
-    /// *请注意：该方法会阻塞运行！*
-    ///
-    pub fn route_pro<F: FnOnce(Vec<u8>) -> (Vec<u8>, bool) + Copy + Send + 'static + UnwindSafe>(&self, route: F) {
-        for stream in self.listener.incoming() {
-            if let Ok(stream) = stream {
-                self.thread.execute(move || Self::handler_pro(stream, route));
-            } else { continue; };
-        };
-    }
-
-    fn handler_pro<F: FnOnce(Vec<u8>) -> (Vec<u8>, bool) + Copy>(stream: TcpStream, route: F) {
-        let mut reader = BufReader::new(&stream);
-        let mut writer = BufWriter::new(&stream);
-
-        loop {
-
-            let (buffer, lens) = match reader.fill_buf() {
-                Ok(x) => (x.to_vec(), x.len()),
-                Err(e) => return Self::return_error(&mut writer, e.to_string().as_str()),
-            };
-
-            if buffer.is_empty() {
-                return Self::return_error(&mut writer, "Empty Input!");
-            };
-
-            let (result, keep_alive) = route(buffer);
-
-            if let Err(e) = writer.write(&result) {
-                return Self::return_error(&mut writer, e.to_string().as_str());
-            }; // 写出处理后的数据
-
-            if keep_alive { // 将数据消耗，防止出现读取重复现象
-                reader.consume(lens);
-            } else { break; };
-
-            if let Err(e) = writer.flush() {
-                return Self::return_error(&mut writer, e.to_string().as_str());
-            } // 立即将数据写出，避免出现无输出现象
-
-        };
-    }
-
-    ///
-    /// 为服务提供路由，并提供服务
-    ///
-    /// 参数：
-    /// - route: 路由函数
-    ///
-    /// 使用该方法，需要定义一个特殊函数：
-    /// ```
-    /// fn route(http_line: (&str, &str), head: HashMap<&str, &str>, body: &str) -> (Vec<u8>, bool) {}
-    /// ```
-    /// 参数：
-    /// - http_line: HTTP请求的头行，包括 `method` `path` `version`
-    ///     - method: 请求方法
-    ///     - path: 请求路径
-    ///     - version: HTTP版本，暂不提供
-    /// - head: HTTP请求的头部信息 (Header)
-    /// - body: 请求主体部分，承载信息
-    ///
-    /// 返回一个元组 `(Vec<u8>, bool)`
-    /// - Vec<u8>: 写入流数据所需的*原始*数据
-    /// - bool: 是否保持持续连接 (`Keep-Alive`)
-    ///
-    /// 该函数的 `http_line` `header` `body` 参数由 `route` 方法提供
-    ///     - http_line: (method: &str, path: &str)
-    ///
-    /// **Example1:**
-    /// ```
-    /// mod salfa_server;
-    /// use std::collections::HashMap;
-    /// use salfa_server::SalServer;
-    ///
-    /// let server = SalServer::new("127.0.0.1:4998", 16);
-    /// serv.route(|http_line: (&str, &str), _header: HashMap<&str, &str>, _body: &str| {
-    ///     (Vec::from("HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n"), false)
-    /// });
-    /// ```
-    ///
-    /// **Example 2:**
-    /// ```
-    /// mod salfa_server;
-    /// use std::collections::HashMap;
-    /// use salfa_server::SalServer;
-    ///
-    /// let server = SalServer::new("127.0.0.1:4998", 16);
-    /// server.route(route);
-    ///
-    /// fn route(http_line: (&str, &str), head: HashMap<&str, &str>, body: &str) -> (Vec<u8>, bool) {
-    ///     let mut buf = Vec::from("HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n");
-    ///     let buff = Vec::from(format!("Http Line: {:?}\r\nHead: {:#?}\r\nBody: {}\r\n", http_line, head, body));
-    ///     buf.extend(buff);
-    ///     return (buf, true)
-    /// }
-    /// ```
-    ///
-    /// > 注意，常见的HTTP方法有：
-    /// `GET POST PUT HEAD DELETE OPTIONS PATCH CONNECT TRACE`
-    ///
-    /// *请注意：该方法会阻塞运行！*
-    ///
-    pub fn route_http<F: FnOnce((&str, &str), HashMap<&str, &str>, &str) -> (Vec<u8>, bool) + Send + 'static + UnwindSafe + Copy>(&self, route: F) {
-        for stream in self.listener.incoming() {
-            if let Ok(stream) = stream {
-                self.thread.execute(move || Self::handler_http(stream, route));
-            } else { continue; };
-        };
-    }
-
-    fn handler_http<F: FnOnce((&str, &str), HashMap<&str, &str>, &str) -> (Vec<u8>, bool) + Copy>(stream: TcpStream, route: F) {
-        let mut reader = BufReader::new(&stream);
-        let mut writer = BufWriter::new(&stream);
-
-        loop {
-
-            let (buffer, lens) = match reader.fill_buf() {
-                Ok(x) => (x, x.len()),
-                Err(e) => return Self::return_error(&mut writer, &*e.to_string()),
-            };
-
-            if buffer.is_empty() {
-                return Self::return_error(&mut writer, "Empty Input!");
-            };
-
-            let buffer = String::from_utf8_lossy(buffer);
-            let Some((headers, body)) = buffer.split_once("\r\n\r\n") else {
-                return Self::return_error(&mut writer, "Non-Standard HTTP Structure!");
-            };
-
-            let mut headers = headers.lines();
-            let Some(http_line) = headers.next() else {
-                return Self::return_error(&mut writer, "Non-Standard HTTP Structure!");
-            };
-
-            let http_line: Vec<&str> = http_line.split_whitespace().collect();
-            let [method, path, _] = http_line[..] else {
-                return Self::return_error(&mut writer, "Non-Standard HTTP Structure!");
-            };
-
-            let mut head = HashMap::new();
-            for header in headers {
-                if let Some(place) = header.find(':') {
-                    let key = header[..place].trim();
-                    let value = header[place+1..].trim();
-                    head.insert(key, value);
-                };
-            };
-
-            let (result, keep_alive) = route((method, path), head, body);
-
-            if let Err(e) = writer.write(&result) {
-                return Self::return_error(&mut writer, &*e.to_string());
-            }; // 写出处理后的数据
-
-            if keep_alive { // 将数据消耗，防止出现读取重复现象
-                reader.consume(lens);
-            } else { break; };
-
-            if let Err(e) = writer.flush() {
-                return Self::return_error(&mut writer, &*e.to_string());
-            } // 立即将数据写出，避免出现无输出现象
-
-        };
-
-    }
-
-    fn return_error(writer: &mut BufWriter<&TcpStream>, err: &str) {
-        let mut res = String::from(
-            "HTTP/1.1 520 LOVE YOU\r\n\
-            Content-Type: text/plain; charset=utf-8\r\n\
-            Connection: close\r\n\r\n"
-        );
-        res.extend([err, "\r\n"]); // 构建应答信息
-
-        if let Err(e) = writer.write(res.as_bytes()) {
-            eprintln!("Write Failure: {}\r\n\tFOR: {e}", err);
-        };
-
-        if let Err(e) = writer.flush() {
-            eprintln!("Flush Failure: {}\r\n\tFOR: {e}", err);
-        } // 立即将数据写出，避免出现无输出现象
-
-    }
-
-}
+//!
+//! 这是一个简易的略有性能的轻量级服务器
+//!
+
+mod thread_limit;
+mod reactor;
+mod static_file;
+pub mod router;
+pub mod response;
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, BufWriter, ErrorKind, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::panic::{AssertUnwindSafe, UnwindSafe};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use self::thread_limit::ThreadLimit;
+use self::router::Router;
+use self::response::Response;
+
+///
+/// HTTP 请求解析结果
+///
+/// `handler_http` 的用户态缓冲解析器用它来表达三种状态：
+/// - Incomplete: 缓冲中的字节尚不足以构成一个完整请求，需继续读取
+/// - Bad: 请求结构非法，应返回错误应答
+/// - Ready: 已切出一个完整请求，附带消费的原始字节数、头部长度与已解码主体
+///
+enum Parse {
+    Incomplete,
+    Bad(&'static str),
+    Ready {
+        consumed: usize,
+        header_len: usize,
+        body: Vec<u8>,
+    },
+}
+
+///
+/// 服务器实例结构体
+///
+/// 用于储存 **线程（thread）** 和 **监听（listener）** 信息
+///
+/// - thread: ThreadLimit
+/// - listener: TcpListener
+/// - inactive: 空闲连接的最长静默时长，`None` 表示永不主动释放
+///
+/// **Example:**
+/// ```
+/// mod salfa_server;
+/// use salfa_server::SalServer;
+/// ```
+///
+pub struct SalServer {
+    thread: ThreadLimit,
+    listener: TcpListener,
+    inactive: Option<Duration>,
+}
+
+impl SalServer {
+
+    ///
+    /// 创建一个新的 `SalServer` 实例
+    ///
+    /// 参数：
+    /// - bind_path: 绑定地址，如：127.0.0.1:8888
+    /// - thread: 线程数量。注意不能为0，否则将***无限期阻塞***
+    ///
+    /// 返回一个新的 `SalServer` 结构体
+    ///
+    /// **Example:**
+    /// ```
+    /// mod salfa_server;
+    /// use salfa_server::SalServer;
+    ///
+    /// let server = SalServer::new("0.0.0.0:8888", 16);
+    /// ```
+    ///
+    /// `bind_path` 接受任何实现了 `ToSocketAddrs` 的类型，既可传字符串也可传
+    /// `(host, port)` 元组。
+    ///
+    pub fn new(bind_path: impl ToSocketAddrs, thread: usize) -> SalServer {
+        let thread = ThreadLimit::new(thread);
+        let listener = TcpListener::bind(bind_path).expect("Error: Couldn't bind port!");
+        SalServer { thread, listener, inactive: None }
+    }
+
+    ///
+    /// 启用 **空闲连接释放（inactive release）**
+    ///
+    /// 参数：
+    /// - secs: 连接允许保持静默的最长秒数
+    ///
+    /// 开启后，`keep_alive` 循环会在每次读取前设置读超时。若一个连接在该时长内
+    /// 始终没有新的可读数据，服务端便主动关闭它，从而归还被占用的线程槽位，
+    /// 避免空闲连接长期占用资源。对仍在缓慢发送数据的活跃连接，则按
+    /// `timeout - (now - last_activity)` 计算剩余预算，不会误伤。
+    ///
+    /// 注意：本设置仅作用于阻塞式服务模式（`route_pro` / `route_http` /
+    /// `route_with` / `serve_dir`）。`route_http_reactor` 以廉价持有大量空闲
+    /// 套接字为设计目标，不套用该超时，故反应堆模式下的 keep-alive 连接不会被
+    /// 主动释放。
+    ///
+    /// 返回配置后的 `SalServer` 结构体（构建器风格）
+    ///
+    /// **Example:**
+    /// ```
+    /// mod salfa_server;
+    /// use salfa_server::SalServer;
+    ///
+    /// let server = SalServer::new("0.0.0.0:8888", 16).enable_inactive_release(30);
+    /// ```
+    ///
+    pub fn enable_inactive_release(mut self, secs: u64) -> SalServer {
+        self.inactive = Some(Duration::from_secs(secs));
+        self
+    }
+
+    ///
+    /// 为服务提供路由，并提供服务（原始方法）
+    ///
+    /// 参数：
+    /// - route: 路由函数
+    ///
+    /// 使用该方法，需要定义一个特殊函数：
+    /// ```
+    /// fn route(buffer: Vec<u8>) -> (Vec<u8>, bool) {}
+    /// ```
+    /// 参数：
+    /// - buffer: 每次请求的原始数据
+    ///
+    /// 返回一个元组 `(Vec<u8>, bool)`
+    /// - Vec<u8>: 写入流数据所需的原始数据
+    /// - bool: 是否保持持续连接 (`Keep-Alive`)
+    ///
+    /// 该函数的 `buffer` 参数由 `route_pro` 方法提供
+    ///
+    /// **Example1:**
+    /// ```
+    /// mod salfa_server;
+    /// use salfa_server::SalServer;
+    ///
+    /// let server = SalServer::new("127.0.0.1:8888", 16);
+    /// server.route_pro(|buffer| {
+    ///     let mut buf = Vec::from(
+    ///         "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n"
+    ///     );
+    ///     buf.extend_from_slice(buffer);
+    ///     return (buf, false);
+    /// });
+    /// ```
+    ///
+    /// **Example 2:**
+    /// ```
+    /// mod salfa_server;
+    /// use salfa_server::SalServer;
+    ///
+    /// let server = SalServer::new("127.0.0.1:8888", 16);
+    /// server.route_pro(route);
+    ///
+    /// fn route(buffer: Vec<u8>) -> (Vec<u8>, bool) {
+    ///     (Vec::from("HTTP/1.1 200 OK\r\n\r\n"), true)
+    /// };
+    /// ```
+    ///
+    /// *请注意：该方法会阻塞运行！*
+    ///
+    pub fn route_pro<F: FnOnce(Vec<u8>) -> (Vec<u8>, bool) + Copy + Send + 'static + UnwindSafe>(&self, route: F) {
+        let inactive = self.inactive;
+        for stream in self.listener.incoming() {
+            if let Ok(stream) = stream {
+                self.thread.execute(move || Self::handler_pro(stream, route, inactive));
+            } else { continue; };
+        };
+    }
+
+    fn handler_pro<F: FnOnce(Vec<u8>) -> (Vec<u8>, bool) + Copy>(stream: TcpStream, route: F, inactive: Option<Duration>) {
+        let mut reader = BufReader::new(&stream);
+        let mut writer = BufWriter::new(&stream);
+        let mut last_activity = Instant::now();
+
+        loop {
+
+            if let Some(timeout) = inactive {
+                match timeout.checked_sub(last_activity.elapsed()) {
+                    Some(budget) if !budget.is_zero() => {
+                        let _ = stream.set_read_timeout(Some(budget));
+                    },
+                    _ => break, // 预算耗尽，连接已静默过久，主动关闭
+                };
+            };
+
+            let (buffer, lens) = match reader.fill_buf() {
+                Ok(x) => (x.to_vec(), x.len()),
+                Err(e) if Self::is_timeout(&e) => break, // 读超时视为连接静默，关闭而非报错
+                Err(e) => return Self::return_error(&mut writer, 500, e.to_string().as_str()),
+            };
+
+            last_activity = Instant::now();
+
+            if buffer.is_empty() {
+                return Self::return_error(&mut writer, 400, "Empty Input!");
+            };
+
+            let (result, keep_alive) = route(buffer);
+
+            if let Err(e) = writer.write_all(&result) {
+                return Self::return_error(&mut writer, 500, e.to_string().as_str());
+            }; // 写出处理后的数据
+
+            if keep_alive { // 将数据消耗，防止出现读取重复现象
+                reader.consume(lens);
+            } else { break; };
+
+            if let Err(e) = writer.flush() {
+                return Self::return_error(&mut writer, 500, e.to_string().as_str());
+            } // 立即将数据写出，避免出现无输出现象
+
+        };
+    }
+
+    ///
+    /// 为服务提供路由，并提供服务
+    ///
+    /// 参数：
+    /// - route: 路由函数
+    ///
+    /// 使用该方法，需要定义一个特殊函数：
+    /// ```
+    /// fn route(http_line: (&str, &str), head: HashMap<&str, &str>, body: &str) -> (Vec<u8>, bool) {}
+    /// ```
+    /// 参数：
+    /// - http_line: HTTP请求的头行，包括 `method` `path` `version`
+    ///     - method: 请求方法
+    ///     - path: 请求路径
+    ///     - version: HTTP版本，暂不提供
+    /// - head: HTTP请求的头部信息 (Header)
+    /// - body: 请求主体部分，承载信息
+    ///
+    /// 返回一个元组 `(Vec<u8>, bool)`
+    /// - Vec<u8>: 写入流数据所需的*原始*数据
+    /// - bool: 是否保持持续连接 (`Keep-Alive`)
+    ///
+    /// 该函数的 `http_line` `header` `body` 参数由 `route` 方法提供
+    ///     - http_line: (method: &str, path: &str)
+    ///
+    /// **Example1:**
+    /// ```
+    /// mod salfa_server;
+    /// use std::collections::HashMap;
+    /// use salfa_server::SalServer;
+    ///
+    /// let server = SalServer::new("127.0.0.1:4998", 16);
+    /// serv.route(|http_line: (&str, &str), _header: HashMap<&str, &str>, _body: &str| {
+    ///     (Vec::from("HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n"), false)
+    /// });
+    /// ```
+    ///
+    /// **Example 2:**
+    /// ```
+    /// mod salfa_server;
+    /// use std::collections::HashMap;
+    /// use salfa_server::SalServer;
+    ///
+    /// let server = SalServer::new("127.0.0.1:4998", 16);
+    /// server.route(route);
+    ///
+    /// fn route(http_line: (&str, &str), head: HashMap<&str, &str>, body: &str) -> (Vec<u8>, bool) {
+    ///     let mut buf = Vec::from("HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n");
+    ///     let buff = Vec::from(format!("Http Line: {:?}\r\nHead: {:#?}\r\nBody: {}\r\n", http_line, head, body));
+    ///     buf.extend(buff);
+    ///     return (buf, true)
+    /// }
+    /// ```
+    ///
+    /// > 注意，常见的HTTP方法有：
+    /// `GET POST PUT HEAD DELETE OPTIONS PATCH CONNECT TRACE`
+    ///
+    /// *请注意：该方法会阻塞运行！*
+    ///
+    pub fn route_http<F: FnOnce((&str, &str), HashMap<&str, &str>, &str) -> (Vec<u8>, bool) + Send + 'static + UnwindSafe + Copy>(&self, route: F) {
+        let inactive = self.inactive;
+        for stream in self.listener.incoming() {
+            if let Ok(stream) = stream {
+                self.thread.execute(move || Self::handler_http(stream, route, inactive));
+            } else { continue; };
+        };
+    }
+
+    ///
+    /// 以 **单反应堆（single-reactor）** 模式提供服务
+    ///
+    /// 参数：
+    /// - route: 路由函数，签名与 `route_http` 完全一致
+    ///
+    /// 与 `route_http` 的阻塞式「一连接一线程」不同，本方法将监听套接字与全部
+    /// 连接设为非阻塞并交由 `poll(2)` 统一驱动：I/O 始终留在反应堆线程上，
+    /// 每个连接各自维护半成品读缓冲与待写缓冲，`EWOULDBLOCK` 只挂起当前连接而
+    /// 不阻塞其余连接；仅把用户 `route` 闭包的 CPU 运算派发到线程池。因此少量
+    /// 线程即可服务大量空闲的 keep-alive 连接，这是当前阻塞式设计做不到的。
+    ///
+    /// **Example:**
+    /// ```
+    /// mod salfa_server;
+    /// use std::collections::HashMap;
+    /// use salfa_server::SalServer;
+    ///
+    /// let server = SalServer::new("127.0.0.1:4998", 16);
+    /// server.route_http_reactor(|_line: (&str, &str), _head: HashMap<&str, &str>, _body: &str| {
+    ///     (Vec::from("HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n"), true)
+    /// });
+    /// ```
+    ///
+    /// *请注意：该方法会阻塞运行！*
+    ///
+    pub fn route_http_reactor<F: FnOnce((&str, &str), HashMap<&str, &str>, &str) -> (Vec<u8>, bool) + Send + 'static + UnwindSafe + Copy>(&self, route: F) {
+        reactor::run(&self.listener, &self.thread, route);
+    }
+
+    ///
+    /// 使用一个 `Router` 分发请求，并提供服务
+    ///
+    /// 参数：
+    /// - router: 预先注册好各路由的 `Router`
+    ///
+    /// 解析请求行后，由路由器按「方法 + 路径模式」查找最佳匹配、抽取路径参数并
+    /// 调用对应处理函数；无匹配时走路由器的回退（404）处理函数。行为与
+    /// `route_http` 一致（含 keep-alive 与空闲释放），区别只在分发方式。
+    ///
+    /// **Example:**
+    /// ```
+    /// mod salfa_server;
+    /// use salfa_server::SalServer;
+    /// use salfa_server::router::Router;
+    ///
+    /// let router = Router::new()
+    ///     .route("GET", "/", |_line, _head, _params, _body| {
+    ///         (Vec::from("HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n"), true)
+    ///     });
+    /// SalServer::new("127.0.0.1:4998", 16).route_with(router);
+    /// ```
+    ///
+    /// *请注意：该方法会阻塞运行！*
+    ///
+    pub fn route_with(&self, router: Router) {
+        let router = Arc::new(router);
+        let inactive = self.inactive;
+        for stream in self.listener.incoming() {
+            if let Ok(stream) = stream {
+                let router = Arc::clone(&router);
+                self.thread.execute(AssertUnwindSafe(move || Self::handler_with(stream, router, inactive)));
+            } else { continue; };
+        };
+    }
+
+    ///
+    /// 以内置静态文件服务提供服务
+    ///
+    /// 参数：
+    /// - mount_path: 挂载前缀，如 `/static`
+    /// - fs_root: 该前缀映射到的文件系统根目录
+    ///
+    /// 把 `mount_path/*` 下的 `GET` 请求映射到 `fs_root` 中的文件，按扩展名附带
+    /// `Content-Type` 与 `Content-Length`，拒绝 `..` 穿越，并支持
+    /// `If-Modified-Since` / `If-None-Match` 的条件请求（命中回送 `304`）。
+    /// 内部构造一个 `Router` 并复用 `route_with` 分发。
+    ///
+    /// **Example:**
+    /// ```
+    /// mod salfa_server;
+    /// use salfa_server::SalServer;
+    ///
+    /// SalServer::new("127.0.0.1:4998", 16).serve_dir("/static", "./public");
+    /// ```
+    ///
+    /// *请注意：该方法会阻塞运行！*
+    ///
+    pub fn serve_dir(&self, mount_path: &str, fs_root: &str) {
+        let root = fs_root.to_string();
+        let mount = mount_path.trim_end_matches('/').to_string();
+        let router = Router::new().route(
+            "GET",
+            &format!("{mount}/*"),
+            move |_line, head, params, _body| {
+                let rel = params.get("*").map(String::as_str).unwrap_or("");
+                static_file::respond(&root, rel, &head)
+            },
+        );
+        self.route_with(router);
+    }
+
+    fn handler_with(stream: TcpStream, router: Arc<Router>, inactive: Option<Duration>) {
+        use std::io::Read;
+
+        let mut writer = BufWriter::new(&stream);
+        let mut last_activity = Instant::now();
+        let mut buf: Vec<u8> = Vec::new();
+        let mut chunk = [0u8; 8192];
+
+        loop {
+
+            let (consumed, header_len, body_bytes) = loop {
+                match Self::parse_request(&buf) {
+                    Parse::Ready { consumed, header_len, body } => break (consumed, header_len, body),
+                    Parse::Bad(msg) => return Self::return_error(&mut writer, 400, msg),
+                    Parse::Incomplete => {
+                        if let Some(timeout) = inactive {
+                            match timeout.checked_sub(last_activity.elapsed()) {
+                                Some(budget) if !budget.is_zero() => {
+                                    let _ = stream.set_read_timeout(Some(budget));
+                                },
+                                _ => return Self::on_idle_timeout(&mut writer, &buf),
+                            };
+                        };
+
+                        match (&stream).read(&mut chunk) {
+                            Ok(0) => return,
+                            Ok(n) => {
+                                buf.extend_from_slice(&chunk[..n]);
+                                last_activity = Instant::now();
+                            },
+                            Err(e) if Self::is_timeout(&e) => return Self::on_idle_timeout(&mut writer, &buf),
+                            Err(e) => return Self::return_error(&mut writer, 500, &e.to_string()),
+                        };
+                    },
+                };
+            };
+
+            let headers = String::from_utf8_lossy(&buf[..header_len]);
+            let mut headers = headers.lines();
+            let Some(http_line) = headers.next() else {
+                return Self::return_error(&mut writer, 400, "Non-Standard HTTP Structure!");
+            };
+
+            let http_line: Vec<&str> = http_line.split_whitespace().collect();
+            let [method, path, _] = http_line[..] else {
+                return Self::return_error(&mut writer, 400, "Non-Standard HTTP Structure!");
+            };
+
+            let mut head = HashMap::new();
+            for header in headers {
+                if let Some(place) = header.find(':') {
+                    head.insert(header[..place].trim(), header[place + 1..].trim());
+                };
+            };
+
+            let body = String::from_utf8_lossy(&body_bytes);
+            let (result, keep_alive) = router.dispatch((method, path), head, &body);
+
+            if let Err(e) = writer.write_all(&result) {
+                return Self::return_error(&mut writer, 500, &e.to_string());
+            };
+
+            if let Err(e) = writer.flush() {
+                return Self::return_error(&mut writer, 500, &e.to_string());
+            };
+
+            buf.drain(..consumed);
+
+            if !keep_alive { break; };
+
+        };
+    }
+
+    fn handler_http<F: FnOnce((&str, &str), HashMap<&str, &str>, &str) -> (Vec<u8>, bool) + Copy>(stream: TcpStream, route: F, inactive: Option<Duration>) {
+        use std::io::Read;
+
+        let mut writer = BufWriter::new(&stream);
+        let mut last_activity = Instant::now();
+
+        // 用户态可增长读缓冲：新读入的字节持续追加到尾部，
+        // 一个完整请求处理后仅将其占用的字节从头部移除，保留后续的流水线请求。
+        let mut buf: Vec<u8> = Vec::new();
+        let mut chunk = [0u8; 8192];
+
+        loop {
+
+            // 尝试从缓冲中切出一个完整请求；不足则继续从流中读取。
+            let parsed = loop {
+                match Self::parse_request(&buf) {
+                    Parse::Ready { consumed, header_len, body } => {
+                        break (consumed, header_len, body);
+                    },
+                    Parse::Bad(msg) => return Self::return_error(&mut writer, 400, msg),
+                    Parse::Incomplete => {
+                        if let Some(timeout) = inactive {
+                            match timeout.checked_sub(last_activity.elapsed()) {
+                                Some(budget) if !budget.is_zero() => {
+                                    let _ = stream.set_read_timeout(Some(budget));
+                                },
+                                _ => return Self::on_idle_timeout(&mut writer, &buf), // 预算耗尽，静默过久
+                            };
+                        };
+
+                        match (&stream).read(&mut chunk) {
+                            Ok(0) => return, // 对端关闭连接
+                            Ok(n) => {
+                                buf.extend_from_slice(&chunk[..n]);
+                                last_activity = Instant::now();
+                            },
+                            Err(e) if Self::is_timeout(&e) => return Self::on_idle_timeout(&mut writer, &buf), // 读超时
+                            Err(e) => return Self::return_error(&mut writer, 500, &e.to_string()),
+                        };
+                    },
+                };
+            };
+
+            let (consumed, header_len, body_bytes) = parsed;
+
+            let headers = String::from_utf8_lossy(&buf[..header_len]);
+            let mut headers = headers.lines();
+            let Some(http_line) = headers.next() else {
+                return Self::return_error(&mut writer, 400, "Non-Standard HTTP Structure!");
+            };
+
+            let http_line: Vec<&str> = http_line.split_whitespace().collect();
+            let [method, path, _] = http_line[..] else {
+                return Self::return_error(&mut writer, 400, "Non-Standard HTTP Structure!");
+            };
+
+            let mut head = HashMap::new();
+            for header in headers {
+                if let Some(place) = header.find(':') {
+                    let key = header[..place].trim();
+                    let value = header[place+1..].trim();
+                    head.insert(key, value);
+                };
+            };
+
+            let body = String::from_utf8_lossy(&body_bytes);
+            let (result, keep_alive) = route((method, path), head, &body);
+
+            if let Err(e) = writer.write_all(&result) {
+                return Self::return_error(&mut writer, 500, &e.to_string());
+            }; // 写出处理后的数据
+
+            if let Err(e) = writer.flush() {
+                return Self::return_error(&mut writer, 500, &e.to_string());
+            } // 立即将数据写出，避免出现无输出现象
+
+            buf.drain(..consumed); // 仅移除本次请求消费的字节，保留流水线中的后续请求
+
+            if !keep_alive { break; };
+
+        };
+
+    }
+
+    ///
+    /// 从用户态缓冲中尝试切出一个完整的 HTTP 请求
+    ///
+    /// 解析规则：
+    /// 1. 先累计字节直到出现 `\r\n\r\n`，以此界定头部块；
+    /// 2. 若存在 `Content-Length: N`，继续累计直到主体满 `N` 字节；
+    /// 3. 若存在 `Transfer-Encoding: chunked`，按块解码：反复读取十六进制长度行、
+    ///    其后对应字节及结尾 CRLF，直到遇到 `0` 长度块为止；
+    /// 4. 二者皆无则视为无主体请求。
+    ///
+    /// 返回 `Parse`：数据不足时为 `Incomplete`，结构非法时为 `Bad`，
+    /// 成功时为 `Ready`，附带本次消费的原始字节数、头部长度与（已解码的）主体。
+    ///
+    fn parse_request(buf: &[u8]) -> Parse {
+        // 定位头部块结束位置 `\r\n\r\n`
+        let Some(header_end) = Self::find(buf, b"\r\n\r\n") else {
+            return Parse::Incomplete;
+        };
+
+        let header_len = header_end; // 头部块不含结尾空行
+        let body_start = header_end + 4;
+        let headers = String::from_utf8_lossy(&buf[..header_len]);
+
+        let mut content_length: Option<usize> = None;
+        let mut chunked = false;
+        for line in headers.lines().skip(1) {
+            let Some(place) = line.find(':') else { continue; };
+            let key = line[..place].trim();
+            let value = line[place + 1..].trim();
+            if key.eq_ignore_ascii_case("content-length") {
+                match value.parse::<usize>() {
+                    Ok(n) => content_length = Some(n),
+                    Err(_) => return Parse::Bad("Invalid Content-Length!"),
+                };
+            } else if key.eq_ignore_ascii_case("transfer-encoding")
+                && value.eq_ignore_ascii_case("chunked")
+            {
+                chunked = true;
+            };
+        };
+
+        if chunked {
+            return Self::decode_chunked(buf, body_start, header_len);
+        };
+
+        if let Some(n) = content_length {
+            if buf.len() < body_start + n {
+                return Parse::Incomplete;
+            };
+            return Parse::Ready {
+                consumed: body_start + n,
+                header_len,
+                body: buf[body_start..body_start + n].to_vec(),
+            };
+        };
+
+        Parse::Ready { consumed: body_start, header_len, body: Vec::new() }
+    }
+
+    ///
+    /// 解码分块传输（chunked transfer）主体
+    ///
+    /// 从 `body_start` 起循环读取一行十六进制长度、随后等量的数据字节及其结尾
+    /// CRLF，直到遇到 `0` 长度块（及其后的结束 CRLF）。数据不足返回 `Incomplete`。
+    ///
+    fn decode_chunked(buf: &[u8], body_start: usize, header_len: usize) -> Parse {
+        let mut pos = body_start;
+        let mut body = Vec::new();
+
+        loop {
+            let Some(rel) = Self::find(&buf[pos..], b"\r\n") else {
+                return Parse::Incomplete;
+            };
+
+            let size_line = String::from_utf8_lossy(&buf[pos..pos + rel]);
+            // 忽略可能存在的块扩展（`;` 之后的内容）
+            let size_hex = size_line.split(';').next().unwrap_or("").trim();
+            let Ok(size) = usize::from_str_radix(size_hex, 16) else {
+                return Parse::Bad("Invalid chunk size!");
+            };
+
+            pos += rel + 2; // 跳过长度行及其 CRLF
+
+            if size == 0 {
+                // 结束块，其后应紧跟一个空行 CRLF（此处不解析 trailer）
+                if buf.len() < pos + 2 {
+                    return Parse::Incomplete;
+                };
+                return Parse::Ready { consumed: pos + 2, header_len, body };
+            };
+
+            if buf.len() < pos + size + 2 {
+                return Parse::Incomplete;
+            };
+
+            body.extend_from_slice(&buf[pos..pos + size]);
+            pos += size + 2; // 跳过数据及其结尾 CRLF
+        };
+    }
+
+    ///
+    /// 在字节切片中查找子串首次出现的位置
+    ///
+    fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        if needle.is_empty() || haystack.len() < needle.len() {
+            return None;
+        };
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle)
+    }
+
+    ///
+    /// 判断读错误是否源自读超时
+    ///
+    /// 在不同平台上读超时会表现为 `WouldBlock` 或 `TimedOut`，二者都应被当作
+    /// 连接静默而非真正的 I/O 错误来处理。
+    ///
+    fn is_timeout(e: &std::io::Error) -> bool {
+        matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+    }
+
+    ///
+    /// 以指定状态码回送一个错误应答
+    ///
+    /// 参数：
+    /// - status: HTTP 状态码，如 `400` `408` `500`
+    /// - err: 作为正文的错误描述
+    ///
+    /// 经由 `Response` 构建器序列化，自动补齐 `Content-Length` 与
+    /// `Connection: close`，取代此前硬编码的 `520 LOVE YOU`。
+    ///
+    fn return_error(writer: &mut BufWriter<&TcpStream>, status: u16, err: &str) {
+        let (res, _) = Response::new(status)
+            .body(format!("{err}\r\n"))
+            .finish(false);
+
+        if let Err(e) = writer.write(&res) {
+            eprintln!("Write Failure: {}\r\n\tFOR: {e}", err);
+        };
+
+        if let Err(e) = writer.flush() {
+            eprintln!("Flush Failure: {}\r\n\tFOR: {e}", err);
+        } // 立即将数据写出，避免出现无输出现象
+
+    }
+
+    ///
+    /// 处理读超时：空闲连接（尚无半成品请求）直接静默关闭，
+    /// 而读到一半的请求超时则回送 `408 Request Timeout`。
+    ///
+    fn on_idle_timeout(writer: &mut BufWriter<&TcpStream>, buf: &[u8]) {
+        if !buf.is_empty() {
+            Self::return_error(writer, 408, "Request Timeout!");
+        };
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_request_no_body() {
+        let raw = b"GET / HTTP/1.1\r\nHost: x\r\n\r\n";
+        match SalServer::parse_request(raw) {
+            Parse::Ready { consumed, body, .. } => {
+                assert_eq!(consumed, raw.len());
+                assert!(body.is_empty());
+            }
+            _ => panic!("expected Ready"),
+        };
+    }
+
+    #[test]
+    fn parse_request_incomplete_headers() {
+        let raw = b"GET / HTTP/1.1\r\nHost: x";
+        assert!(matches!(SalServer::parse_request(raw), Parse::Incomplete));
+    }
+
+    #[test]
+    fn parse_request_content_length() {
+        let raw = b"POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello";
+        match SalServer::parse_request(raw) {
+            Parse::Ready { consumed, body, .. } => {
+                assert_eq!(consumed, raw.len());
+                assert_eq!(body, b"hello");
+            }
+            _ => panic!("expected Ready"),
+        };
+    }
+
+    #[test]
+    fn parse_request_content_length_incomplete() {
+        let raw = b"POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nhel";
+        assert!(matches!(SalServer::parse_request(raw), Parse::Incomplete));
+    }
+
+    #[test]
+    fn parse_request_bad_content_length() {
+        let raw = b"POST / HTTP/1.1\r\nContent-Length: abc\r\n\r\n";
+        assert!(matches!(SalServer::parse_request(raw), Parse::Bad(_)));
+    }
+
+    #[test]
+    fn parse_request_pipelined_preserves_remainder() {
+        let first = b"GET /a HTTP/1.1\r\nHost: x\r\n\r\n";
+        let mut raw = first.to_vec();
+        raw.extend_from_slice(b"GET /b HTTP/1.1\r\nHost: x\r\n\r\n");
+        match SalServer::parse_request(&raw) {
+            Parse::Ready { consumed, .. } => assert_eq!(consumed, first.len()),
+            _ => panic!("expected Ready"),
+        };
+    }
+
+    #[test]
+    fn decode_chunked_body() {
+        let raw = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n\
+                    5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n";
+        match SalServer::parse_request(raw) {
+            Parse::Ready { consumed, body, .. } => {
+                assert_eq!(consumed, raw.len());
+                assert_eq!(body, b"hello world");
+            }
+            _ => panic!("expected Ready"),
+        };
+    }
+
+    #[test]
+    fn decode_chunked_incomplete() {
+        let raw = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhel";
+        assert!(matches!(SalServer::parse_request(raw), Parse::Incomplete));
+    }
+}
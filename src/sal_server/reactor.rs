@@ -0,0 +1,320 @@
+//!
+//! 基于 I/O 多路复用的单反应堆（single-reactor）事件循环
+//!
+//! 与阻塞式「一连接一线程」不同，这里把所有套接字设为非阻塞并统一交给
+//! `poll(2)` 监测就绪状态：监听套接字可读时接受新连接，已连接套接字可读/可写时
+//! 推进各自的连接状态机。每个连接各自保存半成品读缓冲与待写缓冲，因此某个连接
+//! 上的 `EWOULDBLOCK` 只会挂起它自己而不会阻塞其余连接。只有用户的 `route`
+//! 闭包被派发到工作线程池做 CPU 运算，I/O 始终留在反应堆线程上。
+//!
+
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::panic::{AssertUnwindSafe, UnwindSafe};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+use super::response::Response;
+use super::thread_limit::ThreadLimit;
+use super::{Parse, SalServer};
+
+// —— `poll(2)` 的最小 FFI 绑定（保持本 crate 的零依赖风格） ——
+
+const POLLIN: i16 = 0x001;
+const POLLOUT: i16 = 0x004;
+const POLLHUP: i16 = 0x010;
+const POLLERR: i16 = 0x008;
+
+#[repr(C)]
+struct PollFd {
+    fd: RawFd,
+    events: i16,
+    revents: i16,
+}
+
+extern "C" {
+    fn poll(fds: *mut PollFd, nfds: u64, timeout: i32) -> i32;
+}
+
+///
+/// 单个连接的状态机
+///
+/// - read_buf: 尚未解析完的入站字节
+/// - write_buf / write_pos: 待写出的应答及其已写出偏移
+/// - writing: 是否处于写出阶段
+/// - keep_alive: 本次应答写完后是否保持连接
+/// - pending: 已派发给线程池、等待其回送结果的接收端
+///
+struct Conn {
+    stream: TcpStream,
+    read_buf: Vec<u8>,
+    write_buf: Vec<u8>,
+    write_pos: usize,
+    writing: bool,
+    keep_alive: bool,
+    pending: Option<Receiver<(Vec<u8>, bool)>>,
+}
+
+impl Conn {
+    fn new(stream: TcpStream) -> Self {
+        Self {
+            stream,
+            read_buf: Vec::new(),
+            write_buf: Vec::new(),
+            write_pos: 0,
+            writing: false,
+            keep_alive: true,
+            pending: None,
+        }
+    }
+
+    ///
+    /// 本连接当前关心的就绪事件：等待池结果时不关心任何套接字事件，
+    /// 写出阶段关心可写，否则关心可读。
+    ///
+    fn interest(&self) -> i16 {
+        if self.pending.is_some() {
+            0
+        } else if self.writing {
+            POLLOUT
+        } else {
+            POLLIN
+        }
+    }
+
+    ///
+    /// 套接字可读：尽量读入字节后尝试派发缓冲中的完整请求。
+    ///
+    /// 返回 `false` 表示连接应被关闭（对端关闭或发生致命错误）。
+    ///
+    fn on_readable<F>(&mut self, route: F, pool: &ThreadLimit) -> bool
+    where
+        F: FnOnce((&str, &str), HashMap<&str, &str>, &str) -> (Vec<u8>, bool)
+            + Send + 'static + UnwindSafe + Copy,
+    {
+        let mut chunk = [0u8; 8192];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return false, // 对端关闭
+                Ok(n) => self.read_buf.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => return false,
+            };
+        }
+
+        self.try_dispatch(route, pool)
+    }
+
+    ///
+    /// 从已缓冲的字节中切出下一个完整请求并派发给线程池。
+    ///
+    /// 流水线（pipelined）的 keep-alive 客户端会在写出前一个应答后陷入静默，不再
+    /// 触发 `POLLIN`；因此每次写完应答后都须主动回到这里尝试解析剩余的 `read_buf`，
+    /// 而不是只在套接字就绪时解析，否则后续请求将永不被处理。
+    ///
+    /// - `Incomplete`: 字节不足，返回 `true` 等待下一次可读。
+    /// - `Bad`: 结构非法，与 `handler_http` 一致回送 `400` 后关闭（返回 `true`，
+    ///   待应答写完由 `on_writable` 据 `keep_alive` 关闭）。
+    /// - `Ready`: 派发请求并从缓冲头部移除其消费的字节。
+    ///
+    fn try_dispatch<F>(&mut self, route: F, pool: &ThreadLimit) -> bool
+    where
+        F: FnOnce((&str, &str), HashMap<&str, &str>, &str) -> (Vec<u8>, bool)
+            + Send + 'static + UnwindSafe + Copy,
+    {
+        match SalServer::parse_request(&self.read_buf) {
+            Parse::Incomplete => true, // 继续等待更多字节
+            Parse::Bad(msg) => {
+                self.queue_error(400, msg);
+                true
+            }
+            Parse::Ready { consumed, header_len, body } => {
+                self.dispatch(route, pool, header_len, body);
+                self.read_buf.drain(..consumed);
+                true
+            }
+        }
+    }
+
+    ///
+    /// 直接装填一个错误应答进入写出阶段，并在写完后关闭连接。
+    ///
+    fn queue_error(&mut self, status: u16, msg: &str) {
+        let (buf, _) = Response::new(status).body(format!("{msg}\r\n")).finish(false);
+        self.write_buf = buf;
+        self.write_pos = 0;
+        self.writing = true;
+        self.keep_alive = false;
+        self.read_buf.clear();
+    }
+
+    ///
+    /// 将解析好的请求转成拥有所有权的数据，派发到线程池执行 `route`，
+    /// 并保存接收端以便后续取回应答。
+    ///
+    fn dispatch<F>(&mut self, route: F, pool: &ThreadLimit, header_len: usize, body: Vec<u8>)
+    where
+        F: FnOnce((&str, &str), HashMap<&str, &str>, &str) -> (Vec<u8>, bool)
+            + Send + 'static + UnwindSafe + Copy,
+    {
+        let headers = String::from_utf8_lossy(&self.read_buf[..header_len]);
+        let mut lines = headers.lines();
+
+        let line: Vec<&str> = lines.next().unwrap_or("").split_whitespace().collect();
+        let (method, path) = match line[..] {
+            [method, path, _] => (method.to_string(), path.to_string()),
+            _ => (String::new(), String::new()),
+        };
+
+        let mut head: HashMap<String, String> = HashMap::new();
+        for header in lines {
+            if let Some(place) = header.find(':') {
+                head.insert(header[..place].trim().to_string(), header[place + 1..].trim().to_string());
+            };
+        }
+
+        let (tx, rx) = mpsc::channel();
+        pool.execute(AssertUnwindSafe(move || {
+            let refs: HashMap<&str, &str> =
+                head.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+            let body = String::from_utf8_lossy(&body);
+            let out = route((method.as_str(), path.as_str()), refs, &body);
+            let _ = tx.send(out);
+        }));
+
+        self.pending = Some(rx);
+    }
+
+    ///
+    /// 检查线程池是否已回送应答；若是，切换到写出阶段。
+    ///
+    fn poll_compute(&mut self) {
+        if let Some(rx) = &self.pending {
+            match rx.try_recv() {
+                Ok((buf, keep_alive)) => {
+                    self.write_buf = buf;
+                    self.write_pos = 0;
+                    self.writing = true;
+                    self.keep_alive = keep_alive;
+                    self.pending = None;
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => {
+                    // 任务 panic 令发送端被丢弃：回送 500 并关闭，
+                    // 以免已发完整请求的客户端无应答地挂起直至超时。
+                    self.pending = None;
+                    self.queue_error(500, "Internal Server Error!");
+                }
+            };
+        };
+    }
+
+    ///
+    /// 套接字可写：尽量写出待发缓冲。写完后若保持连接则回到读取阶段，
+    /// 否则返回 `false` 要求关闭连接。
+    ///
+    fn on_writable(&mut self) -> bool {
+        while self.write_pos < self.write_buf.len() {
+            match self.stream.write(&self.write_buf[self.write_pos..]) {
+                Ok(0) => return false,
+                Ok(n) => self.write_pos += n,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return true, // 稍后再写
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => return false,
+            };
+        }
+
+        let _ = self.stream.flush();
+        self.writing = false;
+        self.write_buf.clear();
+        self.write_pos = 0;
+        self.keep_alive // 不保持连接则关闭
+    }
+}
+
+///
+/// 运行反应堆事件循环（阻塞，永不返回）
+///
+/// 将监听套接字与所有已接受套接字设为非阻塞，用 `poll(2)` 统一等待就绪事件，
+/// 逐连接推进状态机。`route` 的 CPU 运算经 `pool` 派发到工作线程。
+///
+pub fn run<F>(listener: &TcpListener, pool: &ThreadLimit, route: F)
+where
+    F: FnOnce((&str, &str), HashMap<&str, &str>, &str) -> (Vec<u8>, bool)
+        + Send + 'static + UnwindSafe + Copy,
+{
+    listener.set_nonblocking(true).expect("Error: Couldn't set listener non-blocking!");
+    let lfd = listener.as_raw_fd();
+    let mut conns: HashMap<RawFd, Conn> = HashMap::new();
+
+    loop {
+        // 组装本轮 poll 的关注集：监听套接字 + 每个连接的当前兴趣。
+        let mut fds = Vec::with_capacity(conns.len() + 1);
+        fds.push(PollFd { fd: lfd, events: POLLIN, revents: 0 });
+        for (fd, conn) in &conns {
+            fds.push(PollFd { fd: *fd, events: conn.interest(), revents: 0 });
+        }
+
+        // 50ms 超时，确保即便无套接字事件也能及时回收线程池算好的结果。
+        let ready = unsafe { poll(fds.as_mut_ptr(), fds.len() as u64, 50) };
+        if ready < 0 {
+            continue; // 被信号打断等，下一轮重试
+        }
+
+        // 监听套接字可读：尽量接受所有挂起连接。
+        if fds[0].revents & POLLIN != 0 {
+            loop {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        if stream.set_nonblocking(true).is_ok() {
+                            conns.insert(stream.as_raw_fd(), Conn::new(stream));
+                        };
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(_) => break,
+                };
+            }
+        }
+
+        // 推进各连接状态机。
+        let mut closing = Vec::new();
+        for pfd in &fds[1..] {
+            let Some(conn) = conns.get_mut(&pfd.fd) else { continue; };
+
+            if pfd.revents & (POLLHUP | POLLERR) != 0 {
+                closing.push(pfd.fd);
+                continue;
+            }
+
+            if pfd.revents & POLLIN != 0 && !conn.on_readable(route, pool) {
+                closing.push(pfd.fd);
+                continue;
+            }
+
+            if pfd.revents & POLLOUT != 0 {
+                if !conn.on_writable() {
+                    closing.push(pfd.fd);
+                    continue;
+                }
+                // 应答写完且仍保持连接：立即尝试派发缓冲中流水线的后续请求，
+                // 不依赖下一次 `POLLIN`（对端此刻可能已静默等待应答）。
+                if !conn.writing && conn.pending.is_none() && !conn.try_dispatch(route, pool) {
+                    closing.push(pfd.fd);
+                    continue;
+                }
+            }
+        }
+
+        // 回收线程池已算完的应答，准备写出。
+        for conn in conns.values_mut() {
+            conn.poll_compute();
+        }
+
+        for fd in closing {
+            conns.remove(&fd);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//!
+//! 一个用于安全构造 HTTP 应答的构建器
+//!
+//! 替代手工拼接 `HTTP/1.1 200 OK\r\n...` 字节串并自行计算 `Content-Length` 的
+//! 易错写法。`Response::new(status).header(k, v).body(bytes)` 链式构建，最终由
+//! `finish` 序列化为处理函数所需的 `(Vec<u8>, bool)`，并自动补齐
+//! `Content-Length`、默认的 `Content-Type` 以及与返回布尔值一致的 `Connection`
+//! 头。
+//!
+
+///
+/// HTTP 应答构建器
+///
+/// - status / reason: 状态码及其原因短语
+/// - headers: 按插入顺序保存的响应头
+/// - body: 响应主体字节
+///
+/// **Example:**
+/// ```
+/// mod salfa_server;
+/// use salfa_server::response::Response;
+///
+/// let (buf, keep_alive) = Response::new(200)
+///     .header("Content-Type", "text/plain; charset=utf-8")
+///     .body("hello")
+///     .finish(true);
+/// ```
+///
+pub struct Response {
+    status: u16,
+    reason: &'static str,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Response {
+
+    ///
+    /// 以给定状态码创建一个新的 `Response`
+    ///
+    /// 原因短语由状态码推断，未知状态码回退为 `Unknown`。
+    ///
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            reason: reason(status),
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    ///
+    /// 追加一个响应头（构建器风格）
+    ///
+    pub fn header(mut self, key: &str, value: &str) -> Self {
+        self.headers.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    ///
+    /// 设置响应主体（构建器风格）
+    ///
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    ///
+    /// 序列化为 `(Vec<u8>, bool)`
+    ///
+    /// 参数：
+    /// - keep_alive: 是否保持连接；据此写出 `Connection: keep-alive` 或
+    ///   `Connection: close`，并作为返回元组的第二项。
+    ///
+    /// 自动补齐 `Content-Length`（按主体长度），并在未显式指定时补上默认的
+    /// `Content-Type: text/plain; charset=utf-8`。
+    ///
+    pub fn finish(self, keep_alive: bool) -> (Vec<u8>, bool) {
+        let has_type = self
+            .headers
+            .iter()
+            .any(|(k, _)| k.eq_ignore_ascii_case("content-type"));
+
+        let mut buf = Vec::from(format!("HTTP/1.1 {} {}\r\n", self.status, self.reason));
+
+        for (key, value) in &self.headers {
+            buf.extend_from_slice(format!("{key}: {value}\r\n").as_bytes());
+        }
+
+        if !has_type {
+            buf.extend_from_slice(b"Content-Type: text/plain; charset=utf-8\r\n");
+        };
+
+        buf.extend_from_slice(format!("Content-Length: {}\r\n", self.body.len()).as_bytes());
+        buf.extend_from_slice(
+            if keep_alive { b"Connection: keep-alive\r\n" } else { b"Connection: close\r\n" },
+        );
+        buf.extend_from_slice(b"\r\n");
+        buf.extend(self.body);
+
+        (buf, keep_alive)
+    }
+}
+
+///
+/// 由状态码返回其标准原因短语
+///
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        408 => "Request Timeout",
+        500 => "Internal Server Error",
+        _ => "Unknown",
+    }
+}
@@ -0,0 +1,282 @@
+//!
+//! 一个按「方法 + 路径模式」分发请求的路由器
+//!
+//! 使一个 `SalServer` 能把不同的 `(method, path)` 交给不同处理函数，免去在用户
+//! 代码里手写一个庞大的 `match`。支持三类路径模式：精确路径、结尾通配前缀
+//! （如 `/static/*`）以及具名段捕获（如 `/user/:id`，捕获值随请求一并交给处理
+//! 函数）。无匹配时回退到可配置的 404 处理函数。
+//!
+
+use std::collections::HashMap;
+use std::panic::UnwindSafe;
+
+use super::response::Response;
+
+///
+/// 路由处理函数类型别名
+///
+/// 相比裸路由，额外接收一个 `params` 映射，承载从路径中捕获的具名段
+/// （如 `/user/:id` 中的 `id`）及结尾通配符捕获的剩余路径（键为 `*`）。
+///
+type Handler = Box<
+    dyn Fn((&str, &str), HashMap<&str, &str>, HashMap<String, String>, &str) -> (Vec<u8>, bool)
+        + Send + Sync + UnwindSafe + 'static,
+>;
+
+///
+/// 路径模式中的单个段
+///
+/// - Static: 必须逐字相等的静态段
+/// - Param: 具名捕获段，匹配任意单段并以 `:name` 的 `name` 为键记录其值
+///
+enum Seg {
+    Static(String),
+    Param(String),
+}
+
+///
+/// 一条已注册的路由
+///
+struct Route {
+    method: String,
+    pattern: Vec<Seg>,
+    wildcard: bool, // 模式是否以 `/*` 结尾
+    handler: Handler,
+}
+
+///
+/// 路由器结构体
+///
+/// - routes: 按注册顺序保存的路由表
+/// - not_found: 无匹配时的回退处理函数
+///
+/// **Example:**
+/// ```
+/// mod salfa_server;
+/// use salfa_server::router::Router;
+/// ```
+///
+pub struct Router {
+    routes: Vec<Route>,
+    not_found: Handler,
+}
+
+impl Router {
+
+    ///
+    /// 创建一个新的 `Router` 实例
+    ///
+    /// 默认的回退处理函数返回一个简单的 `404 Not Found` 应答，可用 `fallback`
+    /// 覆盖。
+    ///
+    /// **Example:**
+    /// ```
+    /// mod salfa_server;
+    /// use salfa_server::router::Router;
+    ///
+    /// let router = Router::new();
+    /// ```
+    ///
+    pub fn new() -> Self {
+        let not_found: Handler = Box::new(|_line, _head, _params, _body| {
+            Response::new(404).body("404 Not Found\r\n").finish(false)
+        });
+
+        Self { routes: Vec::new(), not_found }
+    }
+
+    ///
+    /// 注册一条路由（构建器风格）
+    ///
+    /// 参数：
+    /// - method: HTTP 方法，如 `GET` `POST`（匹配时大小写不敏感）
+    /// - pattern: 路径模式，支持 `/exact`、`/static/*`、`/user/:id`
+    /// - handler: 匹配命中时调用的处理函数
+    ///
+    /// **Example:**
+    /// ```
+    /// mod salfa_server;
+    /// use salfa_server::router::Router;
+    ///
+    /// let router = Router::new()
+    ///     .route("GET", "/user/:id", |_line, _head, params, _body| {
+    ///         let id = params.get("id").map(String::as_str).unwrap_or("");
+    ///         (Vec::from(format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}", id.len(), id)), false)
+    ///     });
+    /// ```
+    ///
+    pub fn route<H>(mut self, method: &str, pattern: &str, handler: H) -> Self
+    where
+        H: Fn((&str, &str), HashMap<&str, &str>, HashMap<String, String>, &str) -> (Vec<u8>, bool)
+            + Send + Sync + UnwindSafe + 'static,
+    {
+        let mut segs = Vec::new();
+        let mut wildcard = false;
+        for seg in pattern.split('/').filter(|s| !s.is_empty()) {
+            if seg == "*" {
+                wildcard = true; // 结尾通配，之后不再有段
+                break;
+            } else if let Some(name) = seg.strip_prefix(':') {
+                segs.push(Seg::Param(name.to_string()));
+            } else {
+                segs.push(Seg::Static(seg.to_string()));
+            };
+        }
+
+        self.routes.push(Route {
+            method: method.to_string(),
+            pattern: segs,
+            wildcard,
+            handler: Box::new(handler),
+        });
+        self
+    }
+
+    ///
+    /// 设置无匹配时的回退（404）处理函数（构建器风格）
+    ///
+    pub fn fallback<H>(mut self, handler: H) -> Self
+    where
+        H: Fn((&str, &str), HashMap<&str, &str>, HashMap<String, String>, &str) -> (Vec<u8>, bool)
+            + Send + Sync + UnwindSafe + 'static,
+    {
+        self.not_found = Box::new(handler);
+        self
+    }
+
+    ///
+    /// 按请求查找最佳匹配并调用其处理函数
+    ///
+    /// 先尝试精确/具名段路由，再尝试结尾通配路由；仍无命中则交给回退处理函数。
+    ///
+    pub fn dispatch(
+        &self,
+        http_line: (&str, &str),
+        head: HashMap<&str, &str>,
+        body: &str,
+    ) -> (Vec<u8>, bool) {
+        let (method, path) = http_line;
+        // 先剥除查询串：匹配与静态文件查找只看路径部分，否则 `/static/app.css?v=2`
+        // 会被当成文件名 `app.css?v=2`，而 `/user/:id` 会把 `5?x=1` 整段捕获为 id。
+        let path = path.split('?').next().unwrap_or(path);
+        let segs: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+        // 第一遍只看非通配路由，使其优先级高于结尾通配路由。
+        for wildcard_pass in [false, true] {
+            for route in &self.routes {
+                if route.wildcard != wildcard_pass {
+                    continue;
+                };
+                if !route.method.eq_ignore_ascii_case(method) {
+                    continue;
+                };
+                if let Some(params) = route.matches(&segs) {
+                    return (route.handler)(http_line, head, params, body);
+                };
+            }
+        }
+
+        (self.not_found)(http_line, head, HashMap::new(), body)
+    }
+}
+
+impl Route {
+    ///
+    /// 尝试用本路由的模式匹配请求路径段，命中则返回捕获的参数映射。
+    ///
+    fn matches(&self, segs: &[&str]) -> Option<HashMap<String, String>> {
+        if self.wildcard {
+            if segs.len() < self.pattern.len() {
+                return None;
+            };
+        } else if segs.len() != self.pattern.len() {
+            return None;
+        };
+
+        let mut params = HashMap::new();
+        for (pat, seg) in self.pattern.iter().zip(segs.iter()) {
+            match pat {
+                Seg::Static(s) if s == seg => {}
+                Seg::Static(_) => return None,
+                Seg::Param(name) => {
+                    params.insert(name.clone(), seg.to_string());
+                }
+            };
+        }
+
+        if self.wildcard {
+            // 把通配覆盖的剩余路径段以 `/` 连接后存入 `*`
+            let rest = segs[self.pattern.len()..].join("/");
+            params.insert(String::from("*"), rest);
+        };
+
+        Some(params)
+    }
+}
+
+impl Default for Router {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 用一个把捕获参数原样回送进正文的处理函数，便于断言匹配结果。
+    fn echo_router() -> Router {
+        Router::new()
+            .route("GET", "/", |_l, _h, _p, _b| Response::new(200).body("root").finish(true))
+            .route("GET", "/user/:id", |_l, _h, p, _b| {
+                let id = p.get("id").map(String::as_str).unwrap_or("");
+                Response::new(200).body(format!("id={id}")).finish(true)
+            })
+            .route("GET", "/static/*", |_l, _h, p, _b| {
+                let rest = p.get("*").map(String::as_str).unwrap_or("");
+                Response::new(200).body(format!("file={rest}")).finish(true)
+            })
+    }
+
+    fn body_of(resp: &[u8]) -> String {
+        let text = String::from_utf8_lossy(resp);
+        text.split("\r\n\r\n").nth(1).unwrap_or("").to_string()
+    }
+
+    #[test]
+    fn exact_match() {
+        let (resp, _) = echo_router().dispatch(("GET", "/"), HashMap::new(), "");
+        assert_eq!(body_of(&resp), "root");
+    }
+
+    #[test]
+    fn named_segment_capture() {
+        let (resp, _) = echo_router().dispatch(("GET", "/user/42"), HashMap::new(), "");
+        assert_eq!(body_of(&resp), "id=42");
+    }
+
+    #[test]
+    fn trailing_wildcard_capture() {
+        let (resp, _) = echo_router().dispatch(("GET", "/static/css/app.css"), HashMap::new(), "");
+        assert_eq!(body_of(&resp), "file=css/app.css");
+    }
+
+    #[test]
+    fn query_string_is_stripped() {
+        let (resp, _) = echo_router().dispatch(("GET", "/user/42?v=2"), HashMap::new(), "");
+        assert_eq!(body_of(&resp), "id=42");
+    }
+
+    #[test]
+    fn fallback_on_no_match() {
+        let (resp, keep_alive) = echo_router().dispatch(("GET", "/missing"), HashMap::new(), "");
+        assert!(!keep_alive);
+        assert!(String::from_utf8_lossy(&resp).contains("404 Not Found"));
+    }
+
+    #[test]
+    fn method_mismatch_falls_back() {
+        let (resp, _) = echo_router().dispatch(("POST", "/"), HashMap::new(), "");
+        assert!(String::from_utf8_lossy(&resp).contains("404"));
+    }
+}
@@ -0,0 +1,270 @@
+//!
+//! 内置静态文件服务与条件请求（conditional GET）支持
+//!
+//! 把挂载前缀下的请求路径映射到磁盘文件，按扩展名猜测 `Content-Type` 并附带
+//! `Content-Length` 一次性读入内存后整体回送（非流式、不分块，适合中小资源）；
+//! 拒绝 `..` 目录穿越。同时支持缓存校验握手：应答
+//! 带上由 mtime 生成的 `Last-Modified` 与由「大小 + mtime」构成的弱 `ETag`，
+//! 当请求的 `If-Modified-Since` / `If-None-Match` 仍然匹配时回送 `304 Not
+//! Modified` 空body，避免浏览器重复下载未变更的资源。
+//!
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path};
+use std::time::UNIX_EPOCH;
+
+use super::response::Response;
+
+///
+/// 处理一个落在挂载前缀下的静态文件请求
+///
+/// 参数：
+/// - root: 文件系统根目录
+/// - rel: 去除挂载前缀后的相对路径（即路由通配符 `*` 捕获的剩余路径）
+/// - head: 请求头，用于读取条件请求相关字段
+///
+/// 返回 `(Vec<u8>, bool)`，与其它路由处理函数一致（第二项为是否保持连接）。
+///
+pub fn respond(root: &str, rel: &str, head: &HashMap<&str, &str>) -> (Vec<u8>, bool) {
+    // 拒绝目录穿越：任何 `..` 段都视为非法。
+    let rel_path = Path::new(rel);
+    if rel_path.components().any(|c| matches!(c, Component::ParentDir)) {
+        return simple(403, "403 Forbidden\r\n");
+    };
+
+    let path = Path::new(root).join(rel_path);
+    let Ok(meta) = fs::metadata(&path) else {
+        return simple(404, "404 Not Found\r\n");
+    };
+    if meta.is_dir() {
+        return simple(404, "404 Not Found\r\n");
+    };
+
+    let secs = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let last_modified = httpdate(secs);
+    let etag = format!("W/\"{:x}-{:x}\"", meta.len(), secs); // 弱 ETag：大小 + mtime
+
+    // 条件请求命中则回送 304（ETag 优先于 If-Modified-Since）。
+    // `If-Modified-Since` 按日期比较：只要文件 mtime 不晚于请求携带的时刻即视为未变更，
+    // 因此任何合法的 HTTP-date（含晚于 mtime 者）都能命中，而非仅接受逐字回送的值。
+    let not_modified = match head.get("If-None-Match") {
+        Some(inm) => inm.split(',').any(|t| t.trim() == etag),
+        None => head
+            .get("If-Modified-Since")
+            .and_then(|v| parse_httpdate(v.trim()))
+            .map(|since| secs <= since)
+            .unwrap_or(false),
+    };
+    if not_modified {
+        return Response::new(304)
+            .header("Last-Modified", &last_modified)
+            .header("ETag", &etag)
+            .finish(true);
+    };
+
+    let Ok(body) = fs::read(&path) else {
+        return simple(500, "500 Internal Server Error\r\n");
+    };
+
+    Response::new(200)
+        .header("Content-Type", content_type(&path))
+        .header("Last-Modified", &last_modified)
+        .header("ETag", &etag)
+        .body(body)
+        .finish(true)
+}
+
+///
+/// 构造一个仅含纯文本正文的简单应答
+///
+fn simple(code: u16, body: &str) -> (Vec<u8>, bool) {
+    Response::new(code).body(body.to_string()).finish(true)
+}
+
+///
+/// 按文件扩展名猜测 `Content-Type`
+///
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" => "application/javascript; charset=utf-8",
+        "json" => "application/json; charset=utf-8",
+        "txt" => "text/plain; charset=utf-8",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "wasm" => "application/wasm",
+        _ => "application/octet-stream",
+    }
+}
+
+///
+/// 把 Unix 时间戳（秒）格式化为 RFC 1123 的 HTTP-date（GMT）
+///
+/// 采用 Howard Hinnant 的 `civil_from_days` 算法做日期换算，不引入外部依赖。
+///
+fn httpdate(secs: u64) -> String {
+    const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun",
+        "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let days = (secs / 86400) as i64;
+    let rem = secs % 86400;
+    let (hour, minute, second) = (rem / 3600, rem % 3600 / 60, rem % 60);
+
+    // 1970-01-01 是星期四，对应索引 4。
+    let weekday = (((days % 7) + 4) % 7) as usize;
+
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let year = yoe + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = if month <= 2 { year + 1 } else { year };
+
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[weekday], day, MONTHS[(month - 1) as usize], year, hour, minute, second
+    )
+}
+
+///
+/// 解析 RFC 1123 的 HTTP-date（如 `Wed, 21 Oct 2015 07:28:00 GMT`）为 Unix 时间戳（秒）
+///
+/// 采用 Howard Hinnant 的 `days_from_civil` 算法做日期换算，与 `httpdate` 互逆；
+/// 无法解析（含其它历史日期格式）时返回 `None`，由调用方按「未命中」处理。
+///
+fn parse_httpdate(s: &str) -> Option<u64> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun",
+        "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    // 形如 ["Wed,", "21", "Oct", "2015", "07:28:00", "GMT"]
+    let parts: Vec<&str> = s.split_whitespace().collect();
+    let [_weekday, day, month, year, time, _gmt] = parts[..] else {
+        return None;
+    };
+
+    let day: i64 = day.parse().ok()?;
+    let month = (MONTHS.iter().position(|m| *m == month)? + 1) as i64;
+    let year: i64 = year.parse().ok()?;
+
+    let mut hms = time.split(':');
+    let hour: u64 = hms.next()?.parse().ok()?;
+    let minute: u64 = hms.next()?.parse().ok()?;
+    let second: u64 = hms.next()?.parse().ok()?;
+
+    // days_from_civil：与 httpdate 中的 civil_from_days 互为逆运算。
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+
+    if days < 0 {
+        return None;
+    };
+
+    Some(days as u64 * 86400 + hour * 3600 + minute * 60 + second)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn httpdate_known_value() {
+        // MDN 经典示例：2015-10-21 07:28:00 GMT。
+        assert_eq!(httpdate(1445412480), "Wed, 21 Oct 2015 07:28:00 GMT");
+    }
+
+    #[test]
+    fn parse_httpdate_known_value() {
+        assert_eq!(parse_httpdate("Wed, 21 Oct 2015 07:28:00 GMT"), Some(1445412480));
+    }
+
+    #[test]
+    fn httpdate_roundtrips() {
+        for secs in [0u64, 1, 86399, 1_000_000_000, 1_445_412_480, 2_000_000_000] {
+            assert_eq!(parse_httpdate(&httpdate(secs)), Some(secs), "secs = {secs}");
+        }
+    }
+
+    #[test]
+    fn parse_httpdate_rejects_garbage() {
+        assert_eq!(parse_httpdate("not a date"), None);
+        assert_eq!(parse_httpdate("Wed, 21 Xxx 2015 07:28:00 GMT"), None);
+    }
+
+    fn header_value(resp: &str, key: &str) -> Option<String> {
+        resp.lines().find_map(|line| {
+            let (k, v) = line.split_once(": ")?;
+            k.eq_ignore_ascii_case(key).then(|| v.trim().to_string())
+        })
+    }
+
+    fn unique_dir() -> std::path::PathBuf {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        std::env::temp_dir().join(format!("salstatic-{}-{}", std::process::id(), nanos))
+    }
+
+    #[test]
+    fn conditional_get_handshake() {
+        let dir = unique_dir();
+        fs::create_dir_all(&dir).unwrap();
+        let root = dir.to_str().unwrap();
+        fs::write(dir.join("a.txt"), b"hello").unwrap();
+
+        // 首次请求：200，带上 Last-Modified 与 ETag。
+        let (resp, _) = respond(root, "a.txt", &HashMap::new());
+        let text = String::from_utf8_lossy(&resp).into_owned();
+        assert!(text.starts_with("HTTP/1.1 200"));
+        let last_modified = header_value(&text, "Last-Modified").unwrap();
+        let etag = header_value(&text, "ETag").unwrap();
+
+        // If-Modified-Since 回送同一时刻：命中 304。
+        let mut head = HashMap::new();
+        head.insert("If-Modified-Since", last_modified.as_str());
+        let (resp, _) = respond(root, "a.txt", &head);
+        assert!(String::from_utf8_lossy(&resp).starts_with("HTTP/1.1 304"));
+
+        // 更早的 If-Modified-Since：资源已变更，回 200。
+        let mut head = HashMap::new();
+        head.insert("If-Modified-Since", "Thu, 01 Jan 1970 00:00:00 GMT");
+        let (resp, _) = respond(root, "a.txt", &head);
+        assert!(String::from_utf8_lossy(&resp).starts_with("HTTP/1.1 200"));
+
+        // If-None-Match 命中同一 ETag：304。
+        let mut head = HashMap::new();
+        head.insert("If-None-Match", etag.as_str());
+        let (resp, _) = respond(root, "a.txt", &head);
+        assert!(String::from_utf8_lossy(&resp).starts_with("HTTP/1.1 304"));
+
+        // 目录穿越被拒：403。
+        let (resp, _) = respond(root, "../a.txt", &HashMap::new());
+        assert!(String::from_utf8_lossy(&resp).starts_with("HTTP/1.1 403"));
+
+        let _ = fs::remove_file(dir.join("a.txt"));
+        let _ = fs::remove_dir(&dir);
+    }
+}
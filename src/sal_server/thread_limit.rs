@@ -1,90 +1,142 @@
-//!
-//! 一个对线程数量进行限制的解决方法
-//!
-
-use std::sync::{Arc, Mutex, Condvar};
-use std::{thread, panic::{self, UnwindSafe}};
-
-///
-/// 线程限制结构体
-///
-/// 用于声明 **最大线程数量（max_threads）**
-///
-/// **Example:**
-/// ```
-/// mod thread_limit;
-/// use thread_limit::ThreadLimit;
-/// ```
-///
-pub struct ThreadLimit {
-    max_threads: usize,
-    condvar: Arc<(Mutex<usize>, Condvar)>,
-}
-
-impl ThreadLimit {
-
-    ///
-    /// 创建一个新的 `线程限制` 实例
-    ///
-    /// - 返回 `ThreadLimit` 结构体
-    ///
-    /// **Example:**
-    /// ```
-    /// mod thread_limit;
-    /// use thread_limit::ThreadLimit;
-    ///
-    /// let thread = ThreadLimit::new(4);
-    /// ```
-    ///
-    pub fn new(max_threads: usize) -> Self {
-        Self {
-            max_threads,
-            condvar: Arc::new((Mutex::new(0), Condvar::new())),
-        }
-    }
-
-    ///
-    /// 在所给定的线程数量之内执行任务
-    ///
-    /// **Example:**
-    /// ```
-    /// mod thread_limit;
-    /// use thread_limit::ThreadLimit;
-    ///
-    /// let thread = ThreadLimit::new(4);
-    ///
-    /// thread.execute(move || f(&mut x));
-    /// ```
-    ///
-    /// `f` - 要执行的任务闭包，必须满足 FnOnce() + Send + 'static + UnwindSafe 特征
-    ///
-    /// 请处理好函数 `f` 的错误，以免影响线程的进行；
-    ///
-    /// 若函数 `f` 执行中出现无法恢复的错误，也不会影响线程的回收，保证服务可用。
-    ///
-    pub fn execute<F: FnOnce() + Send + 'static + UnwindSafe>(&self, f: F) {
-        let (lock, cvar) = &*self.condvar;
-        let mut count = lock.lock().expect("Failed to acquire mutex lock");
-
-        while *count >= self.max_threads {
-            count = cvar.wait(count).expect("Failed to wait on condition variable");
-        };
-
-        *count += 1;
-        drop(count);
-
-        let condvar_clone = Arc::clone(&self.condvar);
-
-        thread::spawn(move || {
-
-            if let Err(_) = panic::catch_unwind(|| f()) {};
-
-            let (lock, cvar) = &*condvar_clone;
-            let mut count = lock.lock().expect("Failed to acquire mutex lock");
-            *count -= 1;
-            cvar.notify_one();
-
-        });
-
-    }
-}
+//!
+//! 一个对线程数量进行限制的解决方法
+//!
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::panic::{self, UnwindSafe};
+
+///
+/// 任务类型别名
+///
+/// 每个任务都是一个装箱的 `FnOnce` 闭包，投递到工作队列后由空闲工作线程取出执行
+///
+type Job = Box<dyn FnOnce() + Send + UnwindSafe>;
+
+///
+/// 线程限制结构体
+///
+/// 采用 **半同步/半异步（half-sync/half-reactive）** 模型：在 `new(n)` 时便启动
+/// 恰好 `n` 个常驻工作线程，它们共享同一个任务队列并在条件变量上等待；`execute`
+/// 只负责把任务推入队列并唤醒一个空闲线程，从而避免每次请求都创建新的 OS 线程。
+///
+/// - workers: 常驻工作线程句柄，`Drop` 时逐一 `join`
+/// - queue: 共享任务队列与其条件变量
+/// - stop: 关闭标志，置位后唤醒所有线程以实现优雅退出
+///
+/// **Example:**
+/// ```
+/// mod thread_limit;
+/// use thread_limit::ThreadLimit;
+/// ```
+///
+pub struct ThreadLimit {
+    workers: Vec<JoinHandle<()>>,
+    queue: Arc<(Mutex<VecDeque<Job>>, Condvar)>,
+    stop: Arc<AtomicBool>,
+}
+
+impl ThreadLimit {
+
+    ///
+    /// 创建一个新的 `线程限制` 实例
+    ///
+    /// 启动 `max_threads` 个常驻工作线程，每个线程循环地在共享队列上等待任务。
+    ///
+    /// - 返回 `ThreadLimit` 结构体
+    ///
+    /// **Example:**
+    /// ```
+    /// mod thread_limit;
+    /// use thread_limit::ThreadLimit;
+    ///
+    /// let thread = ThreadLimit::new(4);
+    /// ```
+    ///
+    pub fn new(max_threads: usize) -> Self {
+        let queue: Arc<(Mutex<VecDeque<Job>>, Condvar)> =
+            Arc::new((Mutex::new(VecDeque::new()), Condvar::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let mut workers = Vec::with_capacity(max_threads);
+        for _ in 0..max_threads {
+            let queue = Arc::clone(&queue);
+            let stop = Arc::clone(&stop);
+
+            workers.push(thread::spawn(move || {
+                let (lock, cvar) = &*queue;
+
+                loop {
+                    let mut jobs = lock.lock().expect("Failed to acquire mutex lock");
+
+                    while jobs.is_empty() && !stop.load(Ordering::Acquire) {
+                        jobs = cvar.wait(jobs).expect("Failed to wait on condition variable");
+                    };
+
+                    let job = match jobs.pop_front() {
+                        Some(job) => job,
+                        None => break, // 队列已空且收到关闭信号，退出线程
+                    };
+
+                    drop(jobs); // 及时释放锁，让其它线程领取任务
+
+                    let _ = panic::catch_unwind(job); // 任务 panic 不影响工作线程存活
+                };
+            }));
+        };
+
+        Self { workers, queue, stop }
+    }
+
+    ///
+    /// 在所给定的线程数量之内执行任务
+    ///
+    /// 仅将任务推入共享队列并唤醒一个空闲工作线程，不再创建新线程。
+    ///
+    /// **Example:**
+    /// ```
+    /// mod thread_limit;
+    /// use thread_limit::ThreadLimit;
+    ///
+    /// let thread = ThreadLimit::new(4);
+    ///
+    /// thread.execute(move || f(&mut x));
+    /// ```
+    ///
+    /// `f` - 要执行的任务闭包，必须满足 FnOnce() + Send + 'static + UnwindSafe 特征
+    ///
+    /// 请处理好函数 `f` 的错误，以免影响线程的进行；
+    ///
+    /// 若函数 `f` 执行中出现无法恢复的错误，也不会影响线程的回收，保证服务可用。
+    ///
+    pub fn execute<F: FnOnce() + Send + 'static + UnwindSafe>(&self, f: F) {
+        let (lock, cvar) = &*self.queue;
+        let mut jobs = lock.lock().expect("Failed to acquire mutex lock");
+        jobs.push_back(Box::new(f));
+        drop(jobs);
+        cvar.notify_one();
+    }
+}
+
+///
+/// 优雅关闭：置位关闭标志并唤醒全部工作线程，随后逐一 `join`，
+/// 使进程得以干净退出而不遗留悬挂线程。
+///
+impl Drop for ThreadLimit {
+    fn drop(&mut self) {
+        let (lock, cvar) = &*self.queue;
+        {
+            // 持锁置位，避免工作线程在检查标志与进入等待之间错过唤醒
+            let _jobs = lock.lock().expect("Failed to acquire mutex lock");
+            self.stop.store(true, Ordering::Release);
+        }
+
+        cvar.notify_all();
+
+        for worker in self.workers.drain(..) {
+            worker.join().expect("Failed to join worker thread");
+        };
+    }
+}